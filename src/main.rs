@@ -3,10 +3,16 @@ use colored::Colorize;
 use mime::Mime;
 use reqwest::{header, Client, Response, Url};
 use anyhow::{anyhow, Result};
-use std::{collections::HashMap, str::FromStr};
+use futures_util::StreamExt;
+use once_cell::sync::Lazy;
+use reqwest::cookie::Jar;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::HashMap, fs, path::PathBuf, str::FromStr, sync::Arc};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use syntect::{
-    easy::HighlightLines, 
-    parsing::SyntaxSet, 
+    easy::HighlightLines,
+    parsing::SyntaxSet,
     highlighting::{Style, ThemeSet},
     util::{LinesWithEndings, as_24_bit_terminal_escaped},
 };
@@ -18,63 +24,191 @@ use syntect::{
 #[derive(Parser, Debug)]
 #[clap(version = "1.0", author = "Tianqi Ma <mtqmx3@gmail.com>")]
 struct Opts {
-    #[clap(subcommand)]
-    subcmd: SubCommand,
+    // HTTP 请求方法，支持 get/post/put/delete/patch/head
+    #[clap(arg_enum)]
+    method: Method,
+    // HTTP 请求的 url
+    #[clap(parse(try_from_str = parse_url))]
+    url: String,
+    // 请求项，支持 key=value（JSON 字段）、key:=value（原始 JSON 值）、
+    // key==value（查询参数）、key:value（请求头）四种语法
+    #[clap(parse(try_from_str = parse_request_item))]
+    items: Vec<RequestItem>,
+    // 以流式方式下载响应体到文件，而不是打印到标准输出
+    #[clap(short = 'd', long = "download")]
+    download: bool,
+    // 下载保存路径，缺省时从 Content-Disposition 或 URL 推导文件名
+    #[clap(short = 'o', long = "output")]
+    output: Option<String>,
+    // 会话名，用于在多次调用之间复用请求头和 cookie
+    #[clap(long = "session")]
+    session: Option<String>,
+    // HTTP Basic Auth，格式为 user:pass
+    #[clap(short = 'a', long = "auth")]
+    auth: Option<String>,
+    // Bearer Token，会被设置为 Authorization: Bearer <token>
+    #[clap(long = "bearer")]
+    bearer: Option<String>,
+    // 通过指定的代理转发请求，形如 http://proxy.example.com:8080
+    #[clap(long = "proxy")]
+    proxy: Option<String>,
+    // stdin 请求体的 Content-Type，配合从标准输入读取 body 使用
+    #[clap(short = 't', long = "content-type")]
+    content_type: Option<String>,
+    // 跳过语法高亮，直接打印原始文本，便于管道处理
+    #[clap(long = "raw")]
+    raw: bool,
+    // 只打印将要发送的请求行、请求头与请求体，不真正发出请求
+    #[clap(long = "offline")]
+    offline: bool,
+    // 语法高亮主题，默认为 base16-ocean.dark
+    #[clap(long = "theme", default_value = "base16-ocean.dark")]
+    theme: String,
 }
 
-// get/post
-#[derive(Parser, Debug)]
-enum SubCommand {
-    Get(Get),
-    Post(Post),
+// 只在第一次用到时加载，避免每次打印响应体都重新解析语法/主题定义
+static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
+static THEME_SET: Lazy<ThemeSet> = Lazy::new(ThemeSet::load_defaults);
+
+// 非 tty 输出或设置了 NO_COLOR 时不要上色，方便管道和重定向
+fn color_enabled() -> bool {
+    std::env::var_os("NO_COLOR").is_none() && atty::is(atty::Stream::Stdout)
 }
 
-// get 子命令
+/// 持久化到磁盘的会话状态：上次用过的请求头和收到的 cookie
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Session {
+    headers: HashMap<String, String>,
+    cookies: Vec<String>,
+}
 
-/// feed get with an url and we will retrieve the response for you
-#[derive(Parser, Debug)]
-struct Get{
-    // HTTP 请求的 url
-    #[clap(parse(try_from_str = parse_url))]
-    url: String,
+fn session_path(name: &str) -> Result<PathBuf> {
+    let mut dir = dirs::config_dir().ok_or_else(|| anyhow!("Failed to locate config dir"))?;
+    dir.push("httpie/sessions");
+    fs::create_dir_all(&dir)?;
+    dir.push(format!("{}.json", name));
+    Ok(dir)
 }
 
-// post 子命令
+fn load_session(name: &str) -> Result<Session> {
+    let path = session_path(name)?;
+    if !path.exists() {
+        return Ok(Session::default());
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+}
 
-/// feed post with an url and optional key=value pairs. We will post the data 
-/// as JSON, and retrieve the response for you.
-#[derive(Parser, Debug)]
-struct Post{
-    // HTTP 请求的 url
-    #[clap(parse(try_from_str = parse_url))]
-    url: String,
-    // HTTP 请求的 body
-    #[clap(parse(try_from_str = parse_kv_pair))]
-    body: Vec<KvPair>,
+fn save_session(name: &str, session: &Session) -> Result<()> {
+    let path = session_path(name)?;
+    fs::write(path, serde_json::to_string_pretty(session)?)?;
+    Ok(())
+}
+
+// 把本次实际发出的请求头和服务端返回的 Set-Cookie 合并进已有会话再落盘
+fn persist_session(name: &str, headers: &header::HeaderMap, resp: &Response) -> Result<()> {
+    let mut session = load_session(name)?;
+
+    for (k, v) in headers.iter() {
+        if let Ok(v) = v.to_str() {
+            session.headers.insert(k.to_string(), v.to_string());
+        }
+    }
+
+    for cookie in resp.headers().get_all(header::SET_COOKIE) {
+        if let Ok(cookie) = cookie.to_str() {
+            let name = cookie_name(cookie);
+            session.cookies.retain(|c| cookie_name(c) != name);
+            session.cookies.push(cookie.to_string());
+        }
+    }
+
+    save_session(name, &session)
+}
+
+// Set-Cookie 字符串里 `name=value` 的 name 部分，用于同名覆盖去重
+fn cookie_name(cookie: &str) -> &str {
+    cookie
+        .split(';')
+        .next()
+        .unwrap_or(cookie)
+        .split('=')
+        .next()
+        .unwrap_or(cookie)
+        .trim()
+}
+
+/// 支持的 HTTP 方法
+#[derive(Clone, Copy, Debug, clap::ArgEnum)]
+enum Method {
+    Get,
+    Post,
+    Put,
+    Delete,
+    Patch,
+    Head,
+}
+
+impl From<Method> for reqwest::Method {
+    fn from(m: Method) -> Self {
+        match m {
+            Method::Get => reqwest::Method::GET,
+            Method::Post => reqwest::Method::POST,
+            Method::Put => reqwest::Method::PUT,
+            Method::Delete => reqwest::Method::DELETE,
+            Method::Patch => reqwest::Method::PATCH,
+            Method::Head => reqwest::Method::HEAD,
+        }
+    }
 }
 
-/// 命令行中的 k=v 使用 parse_kv_pair 解析成 KvPair struct
+/// 命令行中的请求项，按分隔符区分出 JSON 字段/原始 JSON 值/查询参数/请求头，
+/// 单独的 `-` 表示请求体来自标准输入
 #[derive(Debug)]
-struct KvPair {
-    k: String,
-    v: String,
+enum RequestItem {
+    JsonField(String, String),
+    JsonRawField(String, Value),
+    QueryParam(String, String),
+    Header(String, String),
+    Stdin,
 }
 
-// 实现 FromStr trait 
-impl FromStr for KvPair {
+// 四种分隔符里谁最先出现谁生效（同一位置优先取更长的操作符），
+// 而不是固定按 `:=`→`==`→`=`→`:` 的顺序逐个尝试——否则像
+// `Cookie:session=abc` 这种 value 里带 `=` 的请求头会被误判成 JSON 字段
+const SEPARATORS: [&str; 4] = [":=", "==", "=", ":"];
+
+impl FromStr for RequestItem {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut split = s.split('=');
         let err = || anyhow!(format!("Failed to parse {}", s));
-        Ok(Self {
-            k: (split.next().ok_or_else(err)?).to_string(),
-            v: (split.next().ok_or_else(err)?).to_string(),
-        })
+
+        if s == "-" {
+            return Ok(Self::Stdin);
+        }
+
+        let (idx, sep) = SEPARATORS
+            .iter()
+            .filter_map(|sep| s.find(sep).map(|idx| (idx, *sep)))
+            .min_by_key(|&(idx, sep)| (idx, std::cmp::Reverse(sep.len())))
+            .ok_or_else(err)?;
+        let (k, v) = (&s[..idx], &s[idx + sep.len()..]);
+
+        match sep {
+            ":=" => {
+                let value: Value = serde_json::from_str(v).map_err(|_| err())?;
+                Ok(Self::JsonRawField(k.to_string(), value))
+            }
+            "==" => Ok(Self::QueryParam(k.to_string(), v.to_string())),
+            "=" => Ok(Self::JsonField(k.to_string(), v.to_string())),
+            ":" => Ok(Self::Header(k.to_string(), v.to_string())),
+            _ => unreachable!(),
+        }
     }
 }
 
-fn parse_kv_pair(s: &str) -> Result<KvPair> {
+fn parse_request_item(s: &str) -> Result<RequestItem> {
     s.parse()
 }
 
@@ -85,23 +219,200 @@ fn parse_url(s: &str) -> Result<String> {
     Ok(s.into())
 }
 
-async fn get(client: Client, args:&Get) -> Result<()> {
-    let resp = client.get(&args.url).send().await?;
-    
-    Ok(print_resp(resp).await?)
+// 默认请求头，可被 `key:value` 请求项传入的同名 header 覆盖
+fn default_headers() -> header::HeaderMap {
+    let mut headers = header::HeaderMap::new();
+    headers.insert("X-POWERED-BY", "Rust".parse().unwrap());
+    headers.insert(header::USER_AGENT, "Rust Httpie".parse().unwrap());
+    headers
+}
+
+// 把 `-a/--auth` 的 user:pass 拆成 basic_auth 需要的 (user, Option<pass>)
+fn split_auth(auth: &str) -> (&str, Option<&str>) {
+    let mut parts = auth.splitn(2, ':');
+    let user = parts.next().unwrap_or_default();
+    let pass = parts.next();
+    (user, pass)
 }
 
-async fn post(client: Client,args: &Post) -> Result<()> {
-    let mut body = HashMap::new();
-    for pair in args.body.iter() {
-        body.insert(&pair.k, &pair.v);
+async fn request(client: Client, opts: &Opts) -> Result<()> {
+    let mut headers = default_headers();
+
+    if let Some(name) = opts.session.as_deref() {
+        for (k, v) in load_session(name)?.headers.iter() {
+            let name = header::HeaderName::from_str(k)?;
+            let value = header::HeaderValue::from_str(v)?;
+            headers.insert(name, value);
+        }
+    }
+
+    let mut json_body = serde_json::Map::new();
+    let mut query = Vec::new();
+    let mut stdin_requested = false;
+
+    for item in opts.items.iter() {
+        match item {
+            RequestItem::JsonField(k, v) => {
+                json_body.insert(k.clone(), Value::String(v.clone()));
+            }
+            RequestItem::JsonRawField(k, v) => {
+                json_body.insert(k.clone(), v.clone());
+            }
+            RequestItem::QueryParam(k, v) => {
+                query.push((k.clone(), v.clone()));
+            }
+            RequestItem::Header(k, v) => {
+                let name = header::HeaderName::from_str(k)?;
+                let value = header::HeaderValue::from_str(v)?;
+                headers.insert(name, value);
+            }
+            RequestItem::Stdin => stdin_requested = true,
+        }
+    }
+
+    // 没有任何请求项、且 stdin 不是 tty 时，也当作要从 stdin 读 body
+    let use_stdin =
+        stdin_requested || (opts.items.is_empty() && !atty::is(atty::Stream::Stdin));
+
+    let sent_headers = headers.clone();
+    let mut req = client
+        .request(opts.method.into(), &opts.url)
+        .headers(headers)
+        .query(&query);
+
+    if use_stdin {
+        let content_type = opts
+            .content_type
+            .clone()
+            .unwrap_or_else(|| mime::APPLICATION_JSON.to_string());
+        let mut raw_body = Vec::new();
+        tokio::io::stdin().read_to_end(&mut raw_body).await?;
+        req = req.header(header::CONTENT_TYPE, content_type).body(raw_body);
+    } else if !json_body.is_empty() {
+        req = req.json(&Value::Object(json_body));
     }
 
-    let resp = client.post(&args.url).json(&body).send().await?;
-    println!("{:?}", resp.text().await?);
+    if let Some(auth) = opts.auth.as_deref() {
+        let (user, pass) = split_auth(auth);
+        req = req.basic_auth(user, pass);
+    }
+
+    if let Some(token) = opts.bearer.as_deref() {
+        req = req.bearer_auth(token);
+    }
+
+    if opts.offline {
+        print_offline(&req.build()?);
+        return Ok(());
+    }
+
+    let resp = req.send().await?;
+
+    if let Some(name) = opts.session.as_deref() {
+        persist_session(name, &sent_headers, &resp)?;
+    }
+
+    if opts.download {
+        download(resp, &opts.url, opts.output.as_deref()).await
+    } else {
+        let raw = opts.raw || !color_enabled();
+        // 只有真的要高亮时才校验 --theme，避免非 tty/管道场景因为主题名无效而白白失败
+        if !raw && !THEME_SET.themes.contains_key(opts.theme.as_str()) {
+            return Err(anyhow!("Unknown theme '{}'", opts.theme));
+        }
+        print_resp(resp, raw, &opts.theme).await
+    }
+}
+
+// --offline 模式下打印将要发出的请求行、请求头和请求体，不实际发送
+fn print_offline(req: &reqwest::Request) {
+    println!("{} {}", req.method(), req.url());
+    for (name, value) in req.headers() {
+        println!("{}: {:?}", name.to_string().green(), value);
+    }
+    println!();
+    if let Some(body) = req.body().and_then(|b| b.as_bytes()) {
+        println!("{}", String::from_utf8_lossy(body));
+    }
+}
+
+// 流式地把响应体写入文件，边写边在 stderr 上汇报进度，避免大文件撑爆内存
+async fn download(resp: Response, url: &str, output: Option<&str>) -> Result<()> {
+    let total = resp.content_length();
+    let filename = output
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| filename_from_resp(&resp, url));
+
+    let mut file = tokio::fs::File::create(&filename).await?;
+    let mut stream = resp.bytes_stream();
+    let mut downloaded: u64 = 0;
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        file.write_all(&chunk).await?;
+        downloaded += chunk.len() as u64;
+        match total {
+            Some(total) if total > 0 => {
+                eprint!(
+                    "\r{} / {} bytes ({:.1}%)",
+                    downloaded,
+                    total,
+                    downloaded as f64 / total as f64 * 100.0
+                );
+            }
+            _ => eprint!("\r{} bytes", downloaded),
+        }
+    }
+    eprintln!();
+    eprintln!("Saved to {}", filename.green());
+
     Ok(())
 }
 
+// 优先从 Content-Disposition 取文件名，否则退回 URL 的最后一段路径；
+// 两者都可能来自服务端/远端输入，统一做一次 sanitize 再当作文件名使用
+fn filename_from_resp(resp: &Response, url: &str) -> String {
+    let name = resp
+        .headers()
+        .get(header::CONTENT_DISPOSITION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_content_disposition_filename)
+        .or_else(|| {
+            Url::parse(url)
+                .ok()
+                .and_then(|u| u.path_segments().and_then(|mut s| s.next_back().map(str::to_string)))
+                .filter(|s| !s.is_empty())
+        })
+        .unwrap_or_else(|| "index.html".to_string());
+
+    sanitize_filename(&name)
+}
+
+// 解析 Content-Disposition 里的 filename/filename* 参数，忽略 size= 等其余参数；
+// filename* 按 RFC 5987 形如 charset'lang'value，这里只取 value 部分
+fn parse_content_disposition_filename(value: &str) -> Option<String> {
+    let parts: Vec<&str> = value.split(';').map(str::trim).collect();
+
+    if let Some(v) = parts.iter().find_map(|p| p.strip_prefix("filename*=")) {
+        let v = v.rsplit('\'').next().unwrap_or(v);
+        return Some(v.trim_matches('"').to_string());
+    }
+
+    parts
+        .iter()
+        .find_map(|p| p.strip_prefix("filename="))
+        .map(|v| v.trim_matches('"').to_string())
+}
+
+// 只取路径最后一段作为文件名，防止 `../../etc/whatever` 这类路径穿越写到 CWD 之外
+fn sanitize_filename(name: &str) -> String {
+    name.rsplit(['/', '\\'])
+        .next()
+        .filter(|s| !s.is_empty() && *s != "." && *s != "..")
+        .unwrap_or("download")
+        .to_string()
+}
+
 // 打印服务器版本号和状态码
 fn print_status(resp: &Response) {
     let status = format!("{:?} {}", resp.version(), resp.status()).blue();
@@ -116,21 +427,24 @@ fn print_headers(resp: &Response) {
     println!()
 }
 
-// 打印服务器返回的 http body
-fn print_body(m: Option<Mime>, body: &str) {
+// 打印服务器返回的 http body，raw 为 true 时跳过语法高亮，便于管道处理
+fn print_body(m: Option<Mime>, body: &str, raw: bool, theme: &str) {
+    if raw {
+        return println!("{}", body);
+    }
     match m {
-        Some(v) if v == mime::APPLICATION_JSON => print_syntect(body, "json"),
-        Some(v) if v == mime::TEXT_HTML => print_syntect(body, "html"),
+        Some(v) if v == mime::APPLICATION_JSON => print_syntect(body, "json", theme),
+        Some(v) if v == mime::TEXT_HTML => print_syntect(body, "html", theme),
         _ => println!("{}", body),
     }
 }
 
-async fn print_resp(resp: Response) -> Result<()> {
+async fn print_resp(resp: Response, raw: bool, theme: &str) -> Result<()> {
     print_status(&resp);
     print_headers(&resp);
     let mime = get_content_type(&resp);
     let body = resp.text().await?;
-    print_body(mime, &body);
+    print_body(mime, &body, raw, theme);
     Ok(())
 }
 
@@ -146,29 +460,153 @@ fn get_content_type(resp: &Response) -> Option<Mime> {
 async fn main() -> Result<()> {
     let opts: Opts = Opts::parse();
 
-    let mut headers = header::HeaderMap::new();
-
-    headers.insert("X-POWERED-BY", "Rust".parse()?);
-    headers.insert(header::USER_AGENT, "Rust Httpie".parse()?);
-    // 生成一个 http 客户端
-    let client = reqwest::Client::builder().default_headers(headers).build()?;
-    let result = match opts.subcmd {
-        SubCommand::Get(ref args) => get(client, args).await?, 
-        SubCommand::Post(ref args) => post(client, args).await?,
-    };
+    // 生成一个 http 客户端，若带了 --session 则把历史 cookie 一并装载进去
+    let mut builder = reqwest::Client::builder().cookie_store(true);
+    if let Some(name) = opts.session.as_deref() {
+        let session = load_session(name)?;
+        let url: Url = opts.url.parse()?;
+        let jar = Jar::default();
+        for cookie in session.cookies.iter() {
+            jar.add_cookie_str(cookie, &url);
+        }
+        builder = builder.cookie_provider(Arc::new(jar));
+    }
+    if let Some(proxy) = opts.proxy.as_deref() {
+        builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+    }
+    let client = builder.build()?;
 
-    Ok(result)
+    request(client, &opts).await
 }
 
-fn print_syntect(s: &str, ext: &str) {
-    let ps = SyntaxSet::load_defaults_newlines();
-    let ts = ThemeSet::load_defaults();
-    let syntax = ps.find_syntax_by_extension(ext).unwrap();
-    let mut h = HighlightLines::new(syntax, &ts.themes["base16-ocean.dark"]);
+// 找不到对应语法或主题时退回纯文本打印，而不是 panic
+fn print_syntect(s: &str, ext: &str, theme: &str) {
+    let syntax = match SYNTAX_SET.find_syntax_by_extension(ext) {
+        Some(syntax) => syntax,
+        None => return println!("{}", s),
+    };
+    let theme = match THEME_SET.themes.get(theme) {
+        Some(theme) => theme,
+        None => return println!("{}", s),
+    };
+
+    let mut h = HighlightLines::new(syntax, theme);
     for line in LinesWithEndings::from(s) {
-        let ranges: Vec<(Style, &str)> = h.highlight(line, &ps);
+        let ranges: Vec<(Style, &str)> = h.highlight(line, &SYNTAX_SET);
         let escaped = as_24_bit_terminal_escaped(&ranges[..], true);
         println!("{}", escaped);
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_json_field() {
+        let item: RequestItem = "name=klaus".parse().unwrap();
+        assert!(matches!(item, RequestItem::JsonField(k, v) if k == "name" && v == "klaus"));
+    }
+
+    #[test]
+    fn parses_json_raw_field() {
+        let item: RequestItem = "age:=30".parse().unwrap();
+        assert!(matches!(item, RequestItem::JsonRawField(k, v) if k == "age" && v == 30));
+    }
+
+    #[test]
+    fn parses_query_param() {
+        let item: RequestItem = "page==2".parse().unwrap();
+        assert!(matches!(item, RequestItem::QueryParam(k, v) if k == "page" && v == "2"));
+    }
+
+    #[test]
+    fn parses_header() {
+        let item: RequestItem = "X-Token:abc".parse().unwrap();
+        assert!(matches!(item, RequestItem::Header(k, v) if k == "X-Token" && v == "abc"));
+    }
+
+    #[test]
+    fn parses_stdin_marker() {
+        let item: RequestItem = "-".parse().unwrap();
+        assert!(matches!(item, RequestItem::Stdin));
+    }
+
+    #[test]
+    fn header_value_containing_equals_is_not_mistaken_for_json_field() {
+        let item: RequestItem = "Cookie:session=abc".parse().unwrap();
+        assert!(matches!(item, RequestItem::Header(k, v) if k == "Cookie" && v == "session=abc"));
+    }
+
+    #[test]
+    fn header_value_containing_query_like_text_is_not_mistaken_for_query_param() {
+        let item: RequestItem = "Referer:https://x?a=b".parse().unwrap();
+        assert!(matches!(item, RequestItem::Header(k, v) if k == "Referer" && v == "https://x?a=b"));
+    }
+
+    #[test]
+    fn rejects_item_without_separator() {
+        assert!("nosep".parse::<RequestItem>().is_err());
+    }
+
+    #[test]
+    fn method_maps_to_reqwest_method() {
+        assert_eq!(reqwest::Method::from(Method::Get), reqwest::Method::GET);
+        assert_eq!(reqwest::Method::from(Method::Post), reqwest::Method::POST);
+        assert_eq!(reqwest::Method::from(Method::Put), reqwest::Method::PUT);
+        assert_eq!(reqwest::Method::from(Method::Delete), reqwest::Method::DELETE);
+        assert_eq!(reqwest::Method::from(Method::Patch), reqwest::Method::PATCH);
+        assert_eq!(reqwest::Method::from(Method::Head), reqwest::Method::HEAD);
+    }
+
+    #[test]
+    fn splits_auth_into_user_and_pass() {
+        assert_eq!(split_auth("alice:secret"), ("alice", Some("secret")));
+    }
+
+    #[test]
+    fn splits_auth_without_password() {
+        assert_eq!(split_auth("alice"), ("alice", None));
+    }
+
+    #[test]
+    fn splits_auth_keeps_colons_in_password() {
+        assert_eq!(split_auth("alice:sec:ret"), ("alice", Some("sec:ret")));
+    }
+
+    #[test]
+    fn content_disposition_prefers_filename_star() {
+        let value = r#"attachment; filename="plain.txt"; filename*=UTF-8''real.txt"#;
+        assert_eq!(
+            parse_content_disposition_filename(value),
+            Some("real.txt".to_string())
+        );
+    }
+
+    #[test]
+    fn content_disposition_falls_back_to_plain_filename() {
+        let value = r#"attachment; filename="report.json"; size=1234"#;
+        assert_eq!(
+            parse_content_disposition_filename(value),
+            Some("report.json".to_string())
+        );
+    }
+
+    #[test]
+    fn content_disposition_without_filename_is_none() {
+        assert_eq!(parse_content_disposition_filename("inline"), None);
+    }
+
+    #[test]
+    fn sanitize_filename_strips_path_traversal() {
+        assert_eq!(sanitize_filename("../../etc/whatever"), "whatever");
+        assert_eq!(sanitize_filename("a/b/c.txt"), "c.txt");
+        assert_eq!(sanitize_filename(".."), "download");
+        assert_eq!(sanitize_filename("report.json"), "report.json");
+    }
+
+    #[test]
+    fn cookie_name_ignores_attributes() {
+        assert_eq!(cookie_name("session=abc; Path=/; HttpOnly"), "session");
+    }
+}